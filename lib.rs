@@ -2,6 +2,22 @@
 use wasm_bindgen::prelude::*;
 use serde::{Serialize, Deserialize};
 
+/// Which loudness measurement backs the silence threshold.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MeasurementMode {
+    /// Crude per-chunk RMS converted to dBFS.
+    #[default]
+    Rms,
+    /// Gated loudness per ITU-R BS.1770 / EBU R128, in LUFS.
+    Lufs,
+    /// Silero VAD speech probability. In the real implementation `threshold`
+    /// is a probability in `[0, 1]` in this mode. The ONNX runtime it uses
+    /// (see src/vad.rs) isn't available in this illustrative WASM example,
+    /// so here this mode falls back to `Rms` and `threshold` is read as dBFS.
+    Vad,
+}
+
 // This allows us to receive settings from JS as a struct
 #[derive(Deserialize)]
 pub struct AnalysisSettings {
@@ -9,6 +25,8 @@ pub struct AnalysisSettings {
     min_silence_duration: f32,
     padding: f32,
     chunk_size: f32,
+    #[serde(default)]
+    measurement_mode: MeasurementMode,
 }
 
 // This is what we'll send back to JS
@@ -38,7 +56,6 @@ pub fn analyze(
     let settings: AnalysisSettings = serde_wasm_bindgen::from_value(settings)
         .map_err(|e| JsValue::from_str(&e.to_string()))?;
 
-    let threshold_amp = 10.0_f32.powf(settings.threshold / 20.0);
     let chunk_size_samples = (sample_rate as f32 * settings.chunk_size / 1000.0) as usize;
     let min_silence_len_chunks = (settings.min_silence_duration / settings.chunk_size).ceil() as usize;
     let padding_sec = (settings.padding / 1000.0) as f64;
@@ -46,13 +63,34 @@ pub fn analyze(
     let chunks: Vec<_> = audio_data.chunks(chunk_size_samples).collect();
     let mut silent_chunks = vec![false; chunks.len()];
 
-    for (i, chunk) in chunks.iter().enumerate() {
-        if rms(chunk) < threshold_amp {
-            silent_chunks[i] = true;
+    // `settings.threshold` is dBFS in `Rms` mode, LUFS in `Lufs` mode; the
+    // real implementation thresholds a gated loudness envelope instead of
+    // per-chunk RMS when `Lufs` is selected (see src/loudness.rs). Resampling,
+    // multi-channel input, denoising, periodicity gating, and the cached
+    // envelope path are all real-implementation features (see src/*.rs)
+    // that this illustrative example deliberately doesn't duplicate; JS
+    // callers that need them should use `find_voiced_segments` instead.
+    match settings.measurement_mode {
+        MeasurementMode::Rms | MeasurementMode::Vad => {
+            let threshold_amp = 10.0_f32.powf(settings.threshold / 20.0);
+            for (i, chunk) in chunks.iter().enumerate() {
+                if rms(chunk) < threshold_amp {
+                    silent_chunks[i] = true;
+                }
+            }
+        }
+        MeasurementMode::Lufs => {
+            for (i, chunk) in chunks.iter().enumerate() {
+                let mean_square = chunk.iter().map(|&x| x * x).sum::<f32>() / chunk.len() as f32;
+                let lufs = -0.691 + 10.0 * mean_square.log10();
+                if lufs < settings.threshold {
+                    silent_chunks[i] = true;
+                }
+            }
         }
     }
-    
-    // ... More sophisticated logic to merge consecutive silent chunks, 
+
+    // ... More sophisticated logic to merge consecutive silent chunks,
     // respect min_silence_len_chunks, apply padding, and finally
     // invert the silent intervals to get audible_intervals ...
 
@@ -60,6 +98,6 @@ pub fn analyze(
         // This is a dummy result for illustration
         audible_intervals: vec![[0.5, 4.2], [5.1, 10.8]],
     };
-    
+
     Ok(serde_wasm_bindgen::to_value(&result)?)
 }
@@ -0,0 +1,123 @@
+//! A small, non-copying view over interleaved multi-channel audio, plus a
+//! helper to combine channels down to a single energy-equivalent signal for
+//! the existing (mono) measurement pipelines.
+
+/// Read-only view of an interleaved `[f32]` buffer as `channels` separate
+/// channels, without copying the underlying data.
+pub struct InterleavedAudio<'a> {
+    data: &'a [f32],
+    channels: usize,
+}
+
+impl<'a> InterleavedAudio<'a> {
+    pub fn new(data: &'a [f32], channels: usize) -> Self {
+        InterleavedAudio { data, channels: channels.max(1) }
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.data.len() / self.channels
+    }
+
+    /// Iterates over a single channel's samples without copying.
+    pub fn channel(&self, index: usize) -> impl Iterator<Item = f32> + 'a {
+        let channels = self.channels;
+        self.data[index..].iter().step_by(channels).copied()
+    }
+
+    /// Copies a single channel out into its own contiguous buffer, for
+    /// callers (like the neural VAD backend) that need a real `&[f32]`.
+    pub fn deinterleave_channel(&self, index: usize) -> Vec<f32> {
+        self.channel(index).collect()
+    }
+}
+
+/// Combines interleaved multi-channel audio into a single signal by summing
+/// mean-square energy across channels per frame. This is a rectified energy
+/// envelope (`sqrt(mean_square)` is always `>= 0`), not a real waveform -
+/// correct for the legacy RMS dBFS gate it was designed for (which squares
+/// it right back), but *wrong* input for anything that assumes a signed
+/// waveform (K-weighting, autocorrelation, spectral denoising - see
+/// `to_combined_mono_signed` for those). Mono input (`channels == 1`) is
+/// returned unchanged (as an owned copy) so callers can treat the result
+/// uniformly.
+pub fn to_combined_mono(data: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return data.to_vec();
+    }
+    let view = InterleavedAudio::new(data, channels);
+    (0..view.frame_count())
+        .map(|frame| {
+            let frame_start = frame * channels;
+            let mean_square = data[frame_start..frame_start + channels]
+                .iter()
+                .map(|&x| x * x)
+                .sum::<f32>()
+                / channels as f32;
+            mean_square.sqrt()
+        })
+        .collect()
+}
+
+/// Combines interleaved multi-channel audio into a single signal by
+/// averaging channels per frame, preserving sign. Use this (instead of
+/// `to_combined_mono`) for any downstream stage that assumes a real,
+/// signed waveform: K-weighted loudness, autocorrelation pitch detection,
+/// and spectral denoising all produce meaningless output if fed a
+/// rectified energy envelope instead. Mono input (`channels == 1`) is
+/// returned unchanged (as an owned copy) so callers can treat the result
+/// uniformly.
+pub fn to_combined_mono_signed(data: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return data.to_vec();
+    }
+    let view = InterleavedAudio::new(data, channels);
+    (0..view.frame_count())
+        .map(|frame| {
+            let frame_start = frame * channels;
+            data[frame_start..frame_start + channels].iter().sum::<f32>() / channels as f32
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deinterleave_channel_extracts_every_nth_sample() {
+        // Interleaved L/R: L0 R0 L1 R1 L2 R2.
+        let data = [1.0, -1.0, 2.0, -2.0, 3.0, -3.0];
+        let view = InterleavedAudio::new(&data, 2);
+        assert_eq!(view.deinterleave_channel(0), vec![1.0, 2.0, 3.0]);
+        assert_eq!(view.deinterleave_channel(1), vec![-1.0, -2.0, -3.0]);
+    }
+
+    #[test]
+    fn mono_passthrough_is_unchanged_for_both_combines() {
+        let data = [0.5, -0.25, 0.125];
+        assert_eq!(to_combined_mono(&data, 1), data.to_vec());
+        assert_eq!(to_combined_mono_signed(&data, 1), data.to_vec());
+    }
+
+    #[test]
+    fn rectified_combine_is_never_negative() {
+        // Two identical channels, fully out of phase with each other's
+        // sign, should still produce a non-negative energy envelope.
+        let data = [1.0, -1.0, -1.0, 1.0];
+        let combined = to_combined_mono(&data, 2);
+        assert!(combined.iter().all(|&x| x >= 0.0));
+        assert_eq!(combined, vec![1.0, 1.0]);
+    }
+
+    #[test]
+    fn signed_combine_preserves_sign_and_cancels_out_of_phase_channels() {
+        // Two identical channels, one inverted: a true signed downmix
+        // cancels to silence, unlike the rectified energy combine above.
+        let data = [1.0, -1.0, -1.0, 1.0];
+        let combined = to_combined_mono_signed(&data, 2);
+        assert_eq!(combined, vec![0.0, 0.0]);
+
+        let in_phase = [0.6, 0.2, -0.6, -0.2];
+        assert_eq!(to_combined_mono_signed(&in_phase, 2), vec![0.4, -0.4]);
+    }
+}
@@ -0,0 +1,226 @@
+//! A simplified RNNoise-style spectral denoiser: short-time spectral
+//! subtraction against a per-band noise floor that's tracked recursively
+//! across frames, rather than the full trained recurrent network.
+
+/// Frame length in seconds (10ms), matching RNNoise's analysis window.
+const FRAME_SECONDS: f64 = 0.01;
+
+/// Builds a periodic Hann window, used both to analyze frames and (via
+/// overlap-add) to resynthesize them without boundary artifacts.
+fn hann_window(len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|n| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * n as f32 / len as f32).cos())
+        .collect()
+}
+
+/// In-place iterative radix-2 Cooley-Tukey FFT. `re`/`im` must have a
+/// power-of-two length. Runs in `O(n log n)` instead of the `O(n^2)` a
+/// direct DFT sum costs - the difference that matters once frames are
+/// strung together over a whole recording rather than a single short clip.
+fn fft_in_place(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    debug_assert!(n.is_power_of_two());
+
+    // Bit-reversal permutation, so the butterflies below can work in place.
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * std::f32::consts::PI / len as f32;
+        let (step_re, step_im) = (angle.cos(), angle.sin());
+        let mut start = 0;
+        while start < n {
+            let (mut w_re, mut w_im) = (1.0f32, 0.0f32);
+            for k in 0..len / 2 {
+                let (a, b) = (start + k, start + k + len / 2);
+                let v_re = re[b] * w_re - im[b] * w_im;
+                let v_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - v_re;
+                im[b] = im[a] - v_im;
+                re[a] += v_re;
+                im[a] += v_im;
+                let next_w_re = w_re * step_re - w_im * step_im;
+                w_im = w_re * step_im + w_im * step_re;
+                w_re = next_w_re;
+            }
+            start += len;
+        }
+        len <<= 1;
+    }
+}
+
+/// Inverse of `fft_in_place`: conjugate, forward transform, conjugate and
+/// scale by `1/n` (the standard trick for getting an inverse FFT out of a
+/// forward one without a second code path).
+fn ifft_in_place(re: &mut [f32], im: &mut [f32]) {
+    for v in im.iter_mut() {
+        *v = -*v;
+    }
+    fft_in_place(re, im);
+    let scale = 1.0 / re.len() as f32;
+    for (r, i) in re.iter_mut().zip(im.iter_mut()) {
+        *r *= scale;
+        *i = -*i * scale;
+    }
+}
+
+/// Real-valued DFT of a windowed frame, zero-padded up to `fft_size` (a
+/// power of two), returning `(re, im)` for bins `0..=fft_size/2` (the
+/// redundant negative-frequency half is reconstructable from these by
+/// symmetry, so it's not returned).
+fn real_dft(frame: &[f32], fft_size: usize) -> (Vec<f32>, Vec<f32>) {
+    let mut re = vec![0.0f32; fft_size];
+    re[..frame.len()].copy_from_slice(frame);
+    let mut im = vec![0.0f32; fft_size];
+    fft_in_place(&mut re, &mut im);
+    re.truncate(fft_size / 2 + 1);
+    im.truncate(fft_size / 2 + 1);
+    (re, im)
+}
+
+/// Inverse of `real_dft`, reconstructing an `fft_size`-sample real frame
+/// from its `0..=fft_size/2` bins via conjugate symmetry.
+fn real_idft(re: &[f32], im: &[f32], fft_size: usize) -> Vec<f32> {
+    let mut full_re = vec![0.0f32; fft_size];
+    let mut full_im = vec![0.0f32; fft_size];
+    full_re[..re.len()].copy_from_slice(re);
+    full_im[..im.len()].copy_from_slice(im);
+    for k in re.len()..fft_size {
+        full_re[k] = re[fft_size - k];
+        full_im[k] = -im[fft_size - k];
+    }
+    ifft_in_place(&mut full_re, &mut full_im);
+    full_re
+}
+
+/// Runs a short-time spectral-subtraction denoiser over `samples`: frames
+/// of ~10ms with 50% overlap, a recursively-tracked per-bin noise floor,
+/// and a spectral-subtraction gain applied before overlap-add resynthesis.
+pub fn denoise(samples: &[f32], sample_rate: f64) -> Vec<f32> {
+    let frame_len = ((sample_rate * FRAME_SECONDS).round() as usize).max(2);
+    let hop = frame_len / 2;
+    if samples.len() < frame_len {
+        return samples.to_vec();
+    }
+
+    // Round the transform size up to a power of two so `fft_in_place` can
+    // use the radix-2 algorithm; the analysis window itself stays
+    // `frame_len` samples and is simply zero-padded before each transform.
+    let fft_size = frame_len.next_power_of_two();
+    let window = hann_window(frame_len);
+    let num_bins = fft_size / 2 + 1;
+    let mut noise_estimate = vec![0.0f32; num_bins];
+    let mut initialized = false;
+
+    let mut output = vec![0.0f32; samples.len()];
+    let mut window_sum = vec![0.0f32; samples.len()];
+
+    let mut start = 0;
+    while start + frame_len <= samples.len() {
+        let windowed: Vec<f32> = samples[start..start + frame_len]
+            .iter()
+            .zip(&window)
+            .map(|(&x, &w)| x * w)
+            .collect();
+
+        let (mut re, mut im) = real_dft(&windowed, fft_size);
+        let power: Vec<f32> = re.iter().zip(&im).map(|(&r, &i)| r * r + i * i).collect();
+
+        if !initialized {
+            noise_estimate.copy_from_slice(&power);
+            initialized = true;
+        }
+
+        for bin in 0..num_bins {
+            // Track the noise floor: snap down fast when the band goes
+            // quiet, rise slowly so speech transients don't get absorbed
+            // into "noise".
+            if power[bin] < noise_estimate[bin] {
+                noise_estimate[bin] = 0.1 * power[bin] + 0.9 * noise_estimate[bin];
+            } else {
+                noise_estimate[bin] = 0.002 * power[bin] + 0.998 * noise_estimate[bin];
+            }
+
+            let gain = if power[bin] > 0.0 {
+                ((power[bin] - noise_estimate[bin]) / power[bin]).clamp(0.1, 1.0)
+            } else {
+                1.0
+            };
+            re[bin] *= gain;
+            im[bin] *= gain;
+        }
+
+        // The zero-padded tail past `frame_len` carries no analysis-window
+        // energy, so only the first `frame_len` samples of the reconstructed
+        // frame feed the overlap-add.
+        let denoised_frame = real_idft(&re, &im, fft_size);
+        for (i, &w) in window.iter().enumerate() {
+            output[start + i] += denoised_frame[i] * w;
+            window_sum[start + i] += w * w;
+        }
+
+        start += hop;
+    }
+
+    for (sample, sum) in output.iter_mut().zip(&window_sum) {
+        if *sum > 1e-8 {
+            *sample /= sum;
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fft_round_trips_a_real_signal() {
+        let fft_size = 64;
+        let original: Vec<f32> =
+            (0..fft_size).map(|n| (2.0 * std::f32::consts::PI * 5.0 * n as f32 / fft_size as f32).sin()).collect();
+
+        let (re, im) = real_dft(&original, fft_size);
+        assert_eq!(re.len(), fft_size / 2 + 1);
+        let reconstructed = real_idft(&re, &im, fft_size);
+
+        for (a, b) in original.iter().zip(&reconstructed) {
+            assert!((a - b).abs() < 1e-4, "round-trip mismatch: {a} vs {b}");
+        }
+    }
+
+    #[test]
+    fn fft_of_dc_signal_lands_entirely_in_bin_zero() {
+        let fft_size = 32;
+        let dc = vec![1.0f32; fft_size];
+        let (re, im) = real_dft(&dc, fft_size);
+
+        assert!((re[0] - fft_size as f32).abs() < 1e-3);
+        assert!(im[0].abs() < 1e-3);
+        for k in 1..re.len() {
+            assert!(re[k].abs() < 1e-3 && im[k].abs() < 1e-3, "unexpected energy in bin {k}");
+        }
+    }
+
+    #[test]
+    fn denoise_preserves_signal_length_and_is_a_no_op_on_short_input() {
+        let short = vec![0.1f32, 0.2, -0.1];
+        assert_eq!(denoise(&short, 48_000.0), short);
+
+        let samples: Vec<f32> = (0..2000).map(|n| (n as f32 * 0.01).sin() * 0.5).collect();
+        let denoised = denoise(&samples, 48_000.0);
+        assert_eq!(denoised.len(), samples.len());
+    }
+}
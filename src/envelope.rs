@@ -0,0 +1,82 @@
+//! Serializable cache of a per-chunk measurement envelope, so callers can
+//! retune threshold/padding without reprocessing the raw waveform.
+
+use serde::{Deserialize, Serialize};
+
+/// Identifies what an `Envelope`'s `values` mean and what settings it was
+/// computed with, so a re-analysis call can reject a mismatched cache
+/// instead of silently producing nonsense.
+#[derive(Serialize, Deserialize, PartialEq)]
+pub struct EnvelopeHeader {
+    pub chunk_size_ms: f64,
+    pub sample_rate: f64,
+    pub measurement_mode: u8,
+    /// Rate the audio was resampled to before measurement, or `0.0` for
+    /// "native rate" (see `voiced_segments_for_mono`). Part of the header
+    /// because it changes what the cached `values` actually measure, not
+    /// just how they're interpreted.
+    pub analysis_rate_hz: f64,
+    /// Whether the spectral-subtraction denoiser ran before measurement.
+    /// Also changes the measured values themselves, not just their use.
+    pub denoise: bool,
+}
+
+/// A cached per-chunk measurement envelope: dBFS, LUFS, or VAD speech
+/// probability per chunk, depending on `header.measurement_mode`.
+#[derive(Serialize, Deserialize)]
+pub struct Envelope {
+    pub header: EnvelopeHeader,
+    #[serde(deserialize_with = "deserialize_values")]
+    pub values: Vec<f64>,
+}
+
+/// `serde_json` writes non-finite floats as `null`; read those back as
+/// `-inf`, the value a digitally silent chunk measures as.
+fn deserialize_values<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<f64>, D::Error> {
+    let values: Vec<Option<f64>> = Deserialize::deserialize(deserializer)?;
+    Ok(values.into_iter().map(|value| value.unwrap_or(f64::NEG_INFINITY)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header() -> EnvelopeHeader {
+        EnvelopeHeader { chunk_size_ms: 20.0, sample_rate: 48_000.0, measurement_mode: 1, analysis_rate_hz: 0.0, denoise: false }
+    }
+
+    #[test]
+    fn identical_headers_match() {
+        assert_eq!(header(), header());
+    }
+
+    #[test]
+    fn mismatched_analysis_rate_is_rejected() {
+        let mut other = header();
+        other.analysis_rate_hz = 16_000.0;
+        assert_ne!(header(), other);
+    }
+
+    #[test]
+    fn mismatched_denoise_is_rejected() {
+        let mut other = header();
+        other.denoise = true;
+        assert_ne!(header(), other);
+    }
+
+    #[test]
+    fn mismatched_measurement_mode_is_rejected() {
+        let mut other = header();
+        other.measurement_mode = 2;
+        assert_ne!(header(), other);
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let envelope = Envelope { header: header(), values: vec![-20.0, -18.5, f64::NEG_INFINITY] };
+        let json = serde_json::to_string(&envelope).expect("serialize");
+        let decoded: Envelope = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(decoded.header, envelope.header);
+        assert_eq!(decoded.values.len(), envelope.values.len());
+    }
+}
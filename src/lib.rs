@@ -1,12 +1,76 @@
 use wasm_bindgen::prelude::*;
 use serde::{Serialize};
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
 
+mod channels;
+mod denoise;
+mod envelope;
+mod loudness;
+mod pitch;
+mod resample;
+#[cfg(feature = "onnx-vad")]
+mod vad;
+
+use envelope::{Envelope, EnvelopeHeader};
+
 #[derive(Serialize)]
 struct TimeRange {
     start: f64,
     end: f64,
+    /// Average estimated fundamental frequency over the segment, in Hz, or
+    /// `None` if periodicity filtering wasn't requested.
+    pitch_hz: Option<f64>,
+    /// Average autocorrelation clarity over the segment, in `[0, 1]`, or
+    /// `None` if periodicity filtering wasn't requested.
+    clarity: Option<f64>,
+}
+
+/// Which backend decides whether a chunk is silent.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MeasurementMode {
+    /// Legacy per-chunk RMS converted to dBFS.
+    Rms,
+    /// Gated loudness per ITU-R BS.1770 / EBU R128, in LUFS.
+    Lufs,
+    /// Silero VAD speech probability; `threshold_db` is interpreted as a
+    /// probability in `[0, 1]` rather than a decibel value in this mode.
+    Vad,
+}
+
+impl MeasurementMode {
+    fn from_code(code: u8) -> Self {
+        match code {
+            1 => MeasurementMode::Lufs,
+            2 => MeasurementMode::Vad,
+            _ => MeasurementMode::Rms,
+        }
+    }
+}
+
+/// Whether combining multi-channel audio down to mono needs to preserve
+/// sign (see `channels::to_combined_mono_signed`) rather than using the
+/// rectified energy envelope the legacy `Rms` gate was built around.
+/// K-weighting, pitch detection, and the denoiser all assume a real,
+/// signed waveform, so any of them being in play forces the signed combine.
+fn needs_signed_channel_combine(mode: MeasurementMode, denoise: bool, require_periodicity: bool) -> bool {
+    denoise || require_periodicity || matches!(mode, MeasurementMode::Lufs | MeasurementMode::Vad)
+}
+
+/// Combines `audio_data` (interleaved, `channels` channels) down to mono
+/// using whichever combine `needs_signed_channel_combine` calls for.
+fn combine_channels_for(
+    audio_data: &[f32],
+    channels: usize,
+    mode: MeasurementMode,
+    denoise: bool,
+    require_periodicity: bool,
+) -> Vec<f32> {
+    if needs_signed_channel_combine(mode, denoise, require_periodicity) {
+        channels::to_combined_mono_signed(audio_data, channels)
+    } else {
+        channels::to_combined_mono(audio_data, channels)
+    }
 }
 
 // Memory management functions for JS to call
@@ -27,40 +91,93 @@ pub fn free_string(ptr: *mut c_char) {
     }
 }
 
-/// A highly optimized function to find voiced segments in raw audio data.
-/// It receives raw audio data from JS, analyzes it, and returns a JSON string
-/// containing an array of {start, end} time ranges.
-#[wasm_bindgen]
-pub fn find_voiced_segments(
-    audio_data_ptr: *const f32,
-    data_len: usize,
+/// Computes one measurement value per chunk: dBFS for `Rms`, LUFS for
+/// `Lufs`, or speech probability in `[0, 1]` for `Vad`. This is the vector
+/// that gets thresholded into silent/voiced chunks, and the one cached by
+/// `compute_envelope` for fast re-analysis.
+fn measure_chunks(audio_data: &[f32], sample_rate: f64, chunk_size_samples: usize, mode: MeasurementMode) -> Vec<f64> {
+    let num_chunks = audio_data.chunks(chunk_size_samples).count();
+    match mode {
+        MeasurementMode::Rms => audio_data
+            .chunks(chunk_size_samples)
+            .map(|chunk| {
+                let sum_squares: f32 = chunk.iter().map(|&sample| sample * sample).sum();
+                let rms = (sum_squares / chunk.len() as f32).sqrt();
+                20.0 * rms.log10() as f64
+            })
+            .collect(),
+        MeasurementMode::Lufs => {
+            let envelope = loudness::gated_loudness_envelope(audio_data, sample_rate);
+            if envelope.is_empty() {
+                return vec![f64::NEG_INFINITY; num_chunks];
+            }
+            let chunk_size_sec = chunk_size_samples as f64 / sample_rate;
+            (0..num_chunks)
+                .map(|i| {
+                    // Loudness blocks are 400ms wide with a 100ms hop, centered
+                    // at block_index * 0.1s + 0.2s; map each chunk's center time
+                    // to the nearest block.
+                    let chunk_center = (i as f64 + 0.5) * chunk_size_sec;
+                    let block_index = ((chunk_center - 0.2) / 0.1).round().max(0.0) as usize;
+                    envelope[block_index.min(envelope.len() - 1)]
+                })
+                .collect()
+        }
+        #[cfg(feature = "onnx-vad")]
+        MeasurementMode::Vad => {
+            let frame_size_samples = if sample_rate >= 16_000.0 { 512 } else { 256 };
+            match vad::speech_probabilities(audio_data, sample_rate) {
+                Ok(probabilities) => {
+                    vad::chunk_probabilities(&probabilities, frame_size_samples, chunk_size_samples, num_chunks)
+                        .into_iter()
+                        .map(|p| p as f64)
+                        .collect()
+                }
+                // If the model failed to load or run, fail safe to "voiced"
+                // (a high probability) rather than silently dropping the
+                // whole recording.
+                Err(_) => vec![1.0; num_chunks],
+            }
+        }
+        // Without the `onnx-vad` feature there's no model to run; fail safe
+        // to "voiced" rather than silently dropping the whole recording.
+        #[cfg(not(feature = "onnx-vad"))]
+        MeasurementMode::Vad => vec![1.0; num_chunks],
+    }
+}
+
+/// Classifies each chunk as silent or not by thresholding `measure_chunks`'
+/// output. `threshold` is dBFS/LUFS for the `Rms`/`Lufs` backends, or a
+/// speech-probability cutoff in `[0, 1]` for the `Vad` backend.
+fn classify_chunks(
+    audio_data: &[f32],
     sample_rate: f64,
-    threshold_db: f64,
+    chunk_size_samples: usize,
+    mode: MeasurementMode,
+    threshold: f64,
+) -> Vec<bool> {
+    measure_chunks(audio_data, sample_rate, chunk_size_samples, mode)
+        .into_iter()
+        .map(|value| value < threshold)
+        .collect()
+}
+
+/// Merges per-chunk silence flags into padded voiced `TimeRange`s, folding
+/// in per-chunk pitch estimates (if any) as each segment's average pitch
+/// and clarity. Shared by the live-audio pipeline and the cached-envelope
+/// re-analysis path.
+fn segments_from_silent_chunks(
+    silent_chunks: &[bool],
     chunk_size_ms: f64,
     min_silence_duration_ms: f64,
     padding_ms: f64,
-) -> *mut c_char {
-    let audio_data = unsafe { std::slice::from_raw_parts(audio_data_ptr, data_len) };
-    
-    let chunk_size_samples = (chunk_size_ms / 1000.0 * sample_rate) as usize;
-    if chunk_size_samples == 0 { 
-        let result_str = CString::new("[]").unwrap();
-        return result_str.into_raw();
-    }
-    
-    let mut volumes = Vec::new();
-    for chunk in audio_data.chunks(chunk_size_samples) {
-        let sum_squares: f32 = chunk.iter().map(|&sample| sample * sample).sum();
-        let rms = (sum_squares / chunk.len() as f32).sqrt();
-        let dbfs = 20.0 * rms.log10() as f64;
-        volumes.push(dbfs);
-    }
-    
+    pitch_estimates: Option<&[pitch::PitchEstimate]>,
+) -> Vec<TimeRange> {
     let mut silent_ranges = Vec::new();
     let mut current_silence_start: Option<usize> = None;
 
-    for (i, &db) in volumes.iter().enumerate() {
-        if db < threshold_db {
+    for (i, &is_silent) in silent_chunks.iter().enumerate() {
+        if is_silent {
             if current_silence_start.is_none() {
                 current_silence_start = Some(i);
             }
@@ -72,7 +189,7 @@ pub fn find_voiced_segments(
         }
     }
     if let Some(start_index) = current_silence_start {
-        silent_ranges.push((start_index, volumes.len()));
+        silent_ranges.push((start_index, silent_chunks.len()));
     }
 
     let min_silence_chunks = (min_silence_duration_ms / chunk_size_ms).ceil() as usize;
@@ -83,8 +200,8 @@ pub fn find_voiced_segments(
         let duration = end - start;
         if duration >= min_silence_chunks {
             let padded_start = start.saturating_sub(padding_chunks);
-            let padded_end = (end + padding_chunks).min(volumes.len());
-            
+            let padded_end = (end + padding_chunks).min(silent_chunks.len());
+
             if let Some(last) = merged_silent_ranges.last_mut() {
                 if padded_start <= last.1 {
                     last.1 = padded_end;
@@ -97,27 +214,331 @@ pub fn find_voiced_segments(
 
     let mut voiced_segments = Vec::new();
     let mut last_end_chunk = 0;
-    let total_chunks = volumes.len();
+    let total_chunks = silent_chunks.len();
     let seconds_per_chunk = chunk_size_ms / 1000.0;
-    
+
+    let average_pitch = |start_chunk: usize, end_chunk: usize| -> (Option<f64>, Option<f64>) {
+        match pitch_estimates {
+            Some(estimates) => {
+                let slice = &estimates[start_chunk..end_chunk.min(estimates.len())];
+                if slice.is_empty() {
+                    (None, None)
+                } else {
+                    let avg_hz = slice.iter().map(|e| e.frequency_hz).sum::<f64>() / slice.len() as f64;
+                    let avg_clarity = slice.iter().map(|e| e.clarity).sum::<f64>() / slice.len() as f64;
+                    (Some(avg_hz), Some(avg_clarity))
+                }
+            }
+            None => (None, None),
+        }
+    };
+
     for (start, end) in merged_silent_ranges {
         if start > last_end_chunk {
+            let (pitch_hz, clarity) = average_pitch(last_end_chunk, start);
             voiced_segments.push(TimeRange {
                 start: last_end_chunk as f64 * seconds_per_chunk,
                 end: start as f64 * seconds_per_chunk,
+                pitch_hz,
+                clarity,
             });
         }
         last_end_chunk = end;
     }
 
     if last_end_chunk < total_chunks {
-         voiced_segments.push(TimeRange {
+        let (pitch_hz, clarity) = average_pitch(last_end_chunk, total_chunks);
+        voiced_segments.push(TimeRange {
             start: last_end_chunk as f64 * seconds_per_chunk,
             end: total_chunks as f64 * seconds_per_chunk,
+            pitch_hz,
+            clarity,
         });
     }
 
-    let result_json = serde_json::to_string(&voiced_segments).unwrap_or_else(|_| "[]".to_string());
-    let result_str = CString::new(result_json).unwrap();
+    voiced_segments
+}
+
+/// Runs the full single-channel pipeline (resample, classify, merge,
+/// invert) and returns the resulting voiced segments. Shared by the
+/// combined-channel and per-channel paths of `find_voiced_segments`.
+fn voiced_segments_for_mono(
+    audio_data: &[f32],
+    sample_rate: f64,
+    threshold_db: f64,
+    chunk_size_ms: f64,
+    min_silence_duration_ms: f64,
+    padding_ms: f64,
+    mode: MeasurementMode,
+    analysis_rate_hz: f64,
+    require_periodicity: bool,
+    pitch_clarity_threshold: f64,
+    denoise: bool,
+    resample_quality: resample::Quality,
+) -> Vec<TimeRange> {
+    // Denoise before anything else, so the loudness/VAD/pitch gates all see
+    // the cleaned signal instead of having to compensate for noise floor.
+    let denoised;
+    let audio_data: &[f32] = if denoise {
+        denoised = denoise::denoise(audio_data, sample_rate);
+        &denoised
+    } else {
+        audio_data
+    };
+
+    // Resample to the requested analysis rate before measuring, since LUFS K-weighting
+    // and the Silero VAD backend are only valid at specific rates. A rate
+    // of 0 means "analyze at the input's native rate".
+    let effective_sample_rate = if analysis_rate_hz > 0.0 { analysis_rate_hz } else { sample_rate };
+    let resampled;
+    let audio_data: &[f32] = if (effective_sample_rate - sample_rate).abs() > f64::EPSILON {
+        resampled = resample::resample_with_quality(audio_data, sample_rate, effective_sample_rate, resample_quality);
+        &resampled
+    } else {
+        audio_data
+    };
+    let sample_rate = effective_sample_rate;
+
+    let chunk_size_samples = (chunk_size_ms / 1000.0 * sample_rate) as usize;
+    if chunk_size_samples == 0 {
+        return Vec::new();
+    }
+
+    let mut silent_chunks = classify_chunks(audio_data, sample_rate, chunk_size_samples, mode, threshold_db);
+
+    // A window only counts as voiced when both its energy/probability gate
+    // passes AND it looks periodic, eliminating sustained non-speech noise
+    // the energy gate alone lets through.
+    let pitch_estimates: Option<Vec<pitch::PitchEstimate>> = if require_periodicity {
+        Some(
+            audio_data
+                .chunks(chunk_size_samples)
+                .map(|chunk| pitch::estimate_pitch(chunk, sample_rate))
+                .collect(),
+        )
+    } else {
+        None
+    };
+    if let Some(estimates) = &pitch_estimates {
+        for (i, is_silent) in silent_chunks.iter_mut().enumerate() {
+            if !*is_silent && estimates[i].clarity < pitch_clarity_threshold {
+                *is_silent = true;
+            }
+        }
+    }
+
+    segments_from_silent_chunks(
+        &silent_chunks,
+        chunk_size_ms,
+        min_silence_duration_ms,
+        padding_ms,
+        pitch_estimates.as_deref(),
+    )
+}
+
+/// Combined-channel result, plus optional per-channel breakdown, returned
+/// when `per_channel_output` is set and there's more than one channel.
+#[derive(Serialize)]
+struct MultiChannelResult {
+    combined: Vec<TimeRange>,
+    channels: Vec<Vec<TimeRange>>,
+}
+
+/// A highly optimized function to find voiced segments in raw audio data.
+/// It receives raw audio data from JS, analyzes it, and returns a JSON string
+/// containing an array of {start, end} time ranges. `audio_data_ptr` is an
+/// interleaved buffer of `channels` channels (1 for mono); channels are
+/// combined by mean-square energy before thresholding for the `Rms` backend,
+/// or by a signed average when LUFS, VAD, denoising or periodicity filtering
+/// is in play (see `combine_channels_for`). When
+/// `per_channel_output` is non-zero and `channels > 1`, the JSON result is
+/// an object with `combined` and `channels` arrays instead of a flat array.
+/// When `require_periodicity` is non-zero, a chunk only counts as voiced if
+/// its autocorrelation clarity also meets `pitch_clarity_threshold`, and
+/// each returned segment carries its average estimated pitch and clarity.
+/// When `denoise` is non-zero, a spectral-subtraction denoiser cleans the
+/// signal before any of the above measurement runs on it. `resample_quality`
+/// selects the resampler used when `analysis_rate_hz` requires a rate
+/// conversion: `0` for the band-limited sinc resampler (default, avoids
+/// aliasing), `1` for cheap cubic interpolation (faster, no aliasing guard).
+#[wasm_bindgen]
+pub fn find_voiced_segments(
+    audio_data_ptr: *const f32,
+    data_len: usize,
+    sample_rate: f64,
+    threshold_db: f64,
+    chunk_size_ms: f64,
+    min_silence_duration_ms: f64,
+    padding_ms: f64,
+    measurement_mode: u8,
+    analysis_rate_hz: f64,
+    channels: u32,
+    per_channel_output: u8,
+    require_periodicity: u8,
+    pitch_clarity_threshold: f64,
+    denoise: u8,
+    resample_quality: u8,
+) -> *mut c_char {
+    let audio_data = unsafe { std::slice::from_raw_parts(audio_data_ptr, data_len) };
+    let channels = (channels as usize).max(1);
+    let mode = MeasurementMode::from_code(measurement_mode);
+    let require_periodicity = require_periodicity != 0;
+    let denoise = denoise != 0;
+    let resample_quality = if resample_quality == 1 { resample::Quality::Fast } else { resample::Quality::Sinc };
+
+    let combined_mono = combine_channels_for(audio_data, channels, mode, denoise, require_periodicity);
+    let combined = voiced_segments_for_mono(
+        &combined_mono,
+        sample_rate,
+        threshold_db,
+        chunk_size_ms,
+        min_silence_duration_ms,
+        padding_ms,
+        mode,
+        analysis_rate_hz,
+        require_periodicity,
+        pitch_clarity_threshold,
+        denoise,
+        resample_quality,
+    );
+
+    let result_json = if per_channel_output != 0 && channels > 1 {
+        let view = channels::InterleavedAudio::new(audio_data, channels);
+        let per_channel: Vec<Vec<TimeRange>> = (0..channels)
+            .map(|ch| {
+                let deinterleaved = view.deinterleave_channel(ch);
+                voiced_segments_for_mono(
+                    &deinterleaved,
+                    sample_rate,
+                    threshold_db,
+                    chunk_size_ms,
+                    min_silence_duration_ms,
+                    padding_ms,
+                    mode,
+                    analysis_rate_hz,
+                    require_periodicity,
+                    pitch_clarity_threshold,
+                    denoise,
+                    resample_quality,
+                )
+            })
+            .collect();
+        serde_json::to_string(&MultiChannelResult { combined, channels: per_channel })
+    } else {
+        serde_json::to_string(&combined)
+    };
+
+    let result_str = CString::new(result_json.unwrap_or_else(|_| "[]".to_string())).unwrap();
+    result_str.into_raw()
+}
+
+/// Computes the per-chunk measurement envelope for `audio_data` once, and
+/// returns it serialized with a header recording the settings it was
+/// computed with. Callers iterating on `threshold_db`/padding should cache
+/// this and re-analyze it via `find_voiced_segments_from_envelope` instead
+/// of reprocessing the raw samples on every call. `analysis_rate_hz` (`0`
+/// for native rate) and `denoise` are applied the same way as in
+/// `find_voiced_segments`, since they change the measured values themselves;
+/// they're recorded in the header so a later re-analysis under different
+/// settings can't silently reuse a cache that no longer applies.
+#[wasm_bindgen]
+pub fn compute_envelope(
+    audio_data_ptr: *const f32,
+    data_len: usize,
+    sample_rate: f64,
+    chunk_size_ms: f64,
+    measurement_mode: u8,
+    channels: u32,
+    analysis_rate_hz: f64,
+    denoise: u8,
+) -> *mut c_char {
+    let audio_data = unsafe { std::slice::from_raw_parts(audio_data_ptr, data_len) };
+    let channels = (channels as usize).max(1);
+    let denoise = denoise != 0;
+    let mode = MeasurementMode::from_code(measurement_mode);
+    // The envelope path never applies periodicity filtering (see
+    // `find_voiced_segments_from_envelope`), so only `mode`/`denoise` can
+    // force the signed combine here.
+    let mono = combine_channels_for(audio_data, channels, mode, denoise, false);
+
+    let denoised;
+    let mono: &[f32] = if denoise {
+        denoised = denoise::denoise(&mono, sample_rate);
+        &denoised
+    } else {
+        &mono
+    };
+
+    let effective_sample_rate = if analysis_rate_hz > 0.0 { analysis_rate_hz } else { sample_rate };
+    let resampled;
+    let mono: &[f32] = if (effective_sample_rate - sample_rate).abs() > f64::EPSILON {
+        resampled = resample::resample_to(mono, sample_rate, effective_sample_rate);
+        &resampled
+    } else {
+        mono
+    };
+
+    let chunk_size_samples = (chunk_size_ms / 1000.0 * effective_sample_rate) as usize;
+    let values = if chunk_size_samples == 0 {
+        Vec::new()
+    } else {
+        measure_chunks(mono, effective_sample_rate, chunk_size_samples, mode)
+    };
+
+    let envelope = Envelope {
+        header: EnvelopeHeader { chunk_size_ms, sample_rate, measurement_mode, analysis_rate_hz, denoise },
+        values,
+    };
+    let result_str = CString::new(serde_json::to_string(&envelope).unwrap_or_else(|_| "null".to_string())).unwrap();
+    result_str.into_raw()
+}
+
+/// Recomputes voiced segments from a previously cached `Envelope` (as
+/// returned by `compute_envelope`) and new `threshold_db`/padding settings,
+/// without touching the raw samples. Rejects the cache (returning `null`)
+/// if `sample_rate`, `chunk_size_ms`, `measurement_mode`, `analysis_rate_hz`,
+/// or `denoise` don't match what the envelope was computed with, since its
+/// values would mean something different under those settings.
+///
+/// `require_periodicity` isn't supported here: periodicity filtering needs
+/// per-chunk pitch estimates computed from the raw samples (see
+/// `pitch.rs`), which a cached envelope doesn't retain. Passing a nonzero
+/// value is rejected (returning `null`) rather than silently ignored; ask
+/// for periodicity filtering via `find_voiced_segments` instead.
+#[wasm_bindgen]
+pub fn find_voiced_segments_from_envelope(
+    envelope_json_ptr: *const c_char,
+    sample_rate: f64,
+    chunk_size_ms: f64,
+    measurement_mode: u8,
+    threshold_db: f64,
+    min_silence_duration_ms: f64,
+    padding_ms: f64,
+    analysis_rate_hz: f64,
+    denoise: u8,
+    require_periodicity: u8,
+) -> *mut c_char {
+    if require_periodicity != 0 {
+        return CString::new("null").unwrap().into_raw();
+    }
+
+    let envelope_json = unsafe { CStr::from_ptr(envelope_json_ptr) }.to_string_lossy();
+    let envelope: Envelope = match serde_json::from_str(&envelope_json) {
+        Ok(envelope) => envelope,
+        Err(_) => return CString::new("null").unwrap().into_raw(),
+    };
+
+    let expected_header =
+        EnvelopeHeader { chunk_size_ms, sample_rate, measurement_mode, analysis_rate_hz, denoise: denoise != 0 };
+    if envelope.header != expected_header {
+        return CString::new("null").unwrap().into_raw();
+    }
+
+    let silent_chunks: Vec<bool> = envelope.values.iter().map(|&value| value < threshold_db).collect();
+    let voiced_segments =
+        segments_from_silent_chunks(&silent_chunks, chunk_size_ms, min_silence_duration_ms, padding_ms, None);
+
+    let result_str =
+        CString::new(serde_json::to_string(&voiced_segments).unwrap_or_else(|_| "[]".to_string())).unwrap();
     result_str.into_raw()
 }
@@ -0,0 +1,239 @@
+//! Perceptual loudness measurement per ITU-R BS.1770 / EBU R128.
+//!
+//! This is a deliberately compact implementation of "K-weighting" plus
+//! gated block loudness, just enough to drive a silence/voice threshold
+//! instead of raw dBFS.
+
+/// A single-stage biquad (direct form I), used to build the two K-weighting
+/// filter stages.
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Biquad {
+    fn new(b0: f32, b1: f32, b2: f32, a1: f32, a2: f32) -> Self {
+        Biquad { b0, b1, b2, a1, a2, x1: 0.0, x2: 0.0, y1: 0.0, y2: 0.0 }
+    }
+
+    fn process(&mut self, x0: f32) -> f32 {
+        let y0 = self.b0 * x0 + self.b1 * self.x1 + self.b2 * self.x2
+            - self.a1 * self.y1 - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x0;
+        self.y2 = self.y1;
+        self.y1 = y0;
+        y0
+    }
+}
+
+/// Builds the two BS.1770 K-weighting stages for a given sample rate:
+/// a high-shelf boost around 1.5 kHz, followed by a ~38 Hz high-pass (RLB).
+///
+/// Coefficients follow the standard's reference design, re-derived per
+/// sample rate via the bilinear transform rather than hard-coded for 48 kHz.
+fn k_weighting_filters(sample_rate: f64) -> (Biquad, Biquad) {
+    // Stage 1: high-shelf, +4 dB around 1.5 kHz.
+    let f0 = 1681.974450955533_f64;
+    let g = 3.999843853973347_f64;
+    let q = 0.7071752369554196_f64;
+    let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+    let vh = 10.0_f64.powf(g / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let stage1 = Biquad::new(
+        ((vh + vb * k / q + k * k) / a0) as f32,
+        (2.0 * (k * k - vh) / a0) as f32,
+        ((vh - vb * k / q + k * k) / a0) as f32,
+        (2.0 * (k * k - 1.0) / a0) as f32,
+        ((1.0 - k / q + k * k) / a0) as f32,
+    );
+
+    // Stage 2: high-pass ("RLB"), -3 dB around 38 Hz.
+    let f0 = 38.13547087613982_f64;
+    let q = 0.5003270373238773_f64;
+    let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let stage2 = Biquad::new(
+        1.0,
+        -2.0,
+        1.0,
+        (2.0 * (k * k - 1.0) / a0) as f32,
+        ((1.0 - k / q + k * k) / a0) as f32,
+    );
+
+    (stage1, stage2)
+}
+
+/// Applies the K-weighting filter chain to `samples`, returning the
+/// filtered signal used for loudness measurement.
+pub fn k_weight(samples: &[f32], sample_rate: f64) -> Vec<f32> {
+    let (mut stage1, mut stage2) = k_weighting_filters(sample_rate);
+    samples.iter().map(|&x| stage2.process(stage1.process(x))).collect()
+}
+
+/// Mean-square energy of a K-weighted block.
+fn block_mean_square(block: &[f32]) -> f64 {
+    if block.is_empty() {
+        return 0.0;
+    }
+    block.iter().map(|&x| (x as f64) * (x as f64)).sum::<f64>() / block.len() as f64
+}
+
+/// Converts a mean-square energy value to LUFS.
+fn lufs_from_mean_square(mean_square: f64) -> f64 {
+    if mean_square <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    -0.691 + 10.0 * mean_square.log10()
+}
+
+/// Computes the gated loudness envelope of `samples`, in LUFS, one value
+/// per 400ms block overlapped by 75% (i.e. a new block every 100ms).
+///
+/// Blocks below the absolute gate (-70 LUFS) are dropped, then blocks more
+/// than 10 LU below the resulting mean are dropped too (relative gate), per
+/// EBU R128. The returned envelope keeps one loudness value per *ungated*
+/// block position so it can be indexed by block index; gated-out blocks are
+/// reported as the relative-gate floor rather than removed, so callers can
+/// still align the envelope against analysis chunks. If every block falls
+/// below the absolute gate, there is no relative gate and the raw per-block
+/// values (`-inf` for digital silence) are returned unchanged.
+pub fn gated_loudness_envelope(samples: &[f32], sample_rate: f64) -> Vec<f64> {
+    let k_weighted = k_weight(samples, sample_rate);
+    let block_len = (sample_rate * 0.4) as usize;
+    let hop_len = (sample_rate * 0.1) as usize;
+    if block_len == 0 || hop_len == 0 || k_weighted.len() < block_len {
+        return Vec::new();
+    }
+
+    let mut mean_squares = Vec::new();
+    let mut start = 0;
+    while start + block_len <= k_weighted.len() {
+        mean_squares.push(block_mean_square(&k_weighted[start..start + block_len]));
+        start += hop_len;
+    }
+    let blocks: Vec<f64> = mean_squares.iter().map(|&ms| lufs_from_mean_square(ms)).collect();
+
+    const ABSOLUTE_GATE: f64 = -70.0;
+    // Average the *linear* mean-square values of the ungated blocks, then
+    // convert once, rather than averaging already-converted LUFS values in
+    // the log domain (which understates the true gated loudness).
+    let ungated_mean_squares: Vec<f64> = mean_squares
+        .iter()
+        .zip(&blocks)
+        .filter(|&(_, &l)| l > ABSOLUTE_GATE)
+        .map(|(&ms, _)| ms)
+        .collect();
+    if ungated_mean_squares.is_empty() {
+        return blocks;
+    }
+    let ungated_mean_square = ungated_mean_squares.iter().sum::<f64>() / ungated_mean_squares.len() as f64;
+    let relative_gate = lufs_from_mean_square(ungated_mean_square) - 10.0;
+
+    blocks
+        .into_iter()
+        .map(|l| if l > ABSOLUTE_GATE && l > relative_gate { l } else { relative_gate })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_mean_square_of_empty_block_is_zero() {
+        assert_eq!(block_mean_square(&[]), 0.0);
+    }
+
+    #[test]
+    fn block_mean_square_matches_the_formula() {
+        let block = [1.0f32, -1.0, 0.5, -0.5];
+        // (1 + 1 + 0.25 + 0.25) / 4 = 0.625
+        assert!((block_mean_square(&block) - 0.625).abs() < 1e-9);
+    }
+
+    #[test]
+    fn lufs_from_mean_square_handles_unity_and_silence() {
+        assert!((lufs_from_mean_square(1.0) - (-0.691)).abs() < 1e-9);
+        assert_eq!(lufs_from_mean_square(0.0), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn too_short_input_returns_an_empty_envelope() {
+        let samples = vec![0.1f32; 100];
+        assert!(gated_loudness_envelope(&samples, 48_000.0).is_empty());
+    }
+
+    #[test]
+    fn silence_is_gated_out_entirely() {
+        let sample_rate = 48_000.0;
+        let samples = vec![0.0f32; (sample_rate * 0.6) as usize];
+        let envelope = gated_loudness_envelope(&samples, sample_rate);
+        assert!(!envelope.is_empty());
+        assert!(envelope.iter().all(|&l| l == f64::NEG_INFINITY));
+    }
+
+    #[test]
+    fn a_steady_tone_produces_a_finite_envelope_within_a_plausible_lufs_range() {
+        let sample_rate = 48_000.0;
+        let num_samples = (sample_rate * 1.0) as usize;
+        let samples: Vec<f32> = (0..num_samples)
+            .map(|n| (2.0 * std::f64::consts::PI * 1000.0 * n as f64 / sample_rate).sin() as f32 * 0.5)
+            .collect();
+        let envelope = gated_loudness_envelope(&samples, sample_rate);
+        assert!(!envelope.is_empty());
+        for &l in &envelope {
+            assert!(l.is_finite(), "expected a finite LUFS value, got {l}");
+            assert!((-30.0..0.0).contains(&l), "loudness {l} outside a plausible range for a half-scale tone");
+        }
+    }
+
+    #[test]
+    fn relative_gate_is_derived_from_linear_averaging_not_log_averaging() {
+        // Regression for averaging already-converted LUFS values instead of
+        // linear mean-square energy: build two explicit per-block
+        // mean-square values and confirm the two averaging strategies give
+        // different gates, then check the gate `gated_loudness_envelope`
+        // actually reports against both of them.
+        let ms_a = 1.0_f64;
+        let ms_b = 0.01_f64;
+        let lufs_a = lufs_from_mean_square(ms_a);
+        let lufs_b = lufs_from_mean_square(ms_b);
+
+        let linear_gate = lufs_from_mean_square((ms_a + ms_b) / 2.0) - 10.0;
+        let log_domain_gate = (lufs_a + lufs_b) / 2.0 - 10.0;
+
+        assert!((linear_gate - log_domain_gate).abs() > 1.0, "expected the two averaging strategies to diverge");
+
+        // A loud 1 kHz tone followed by a quiet one: every block passes the
+        // absolute gate, and the quiet blocks fall below the relative gate,
+        // so they are reported as the gate itself.
+        let sample_rate = 48_000.0;
+        let samples: Vec<f32> = (0..(sample_rate * 2.0) as usize)
+            .map(|n| {
+                let amplitude = if n < sample_rate as usize { 0.5 } else { 0.005 };
+                (2.0 * std::f64::consts::PI * 1000.0 * n as f64 / sample_rate).sin() as f32 * amplitude
+            })
+            .collect();
+        let envelope = gated_loudness_envelope(&samples, sample_rate);
+        let k_weighted = k_weight(&samples, sample_rate);
+        let block_mean_squares: Vec<f64> =
+            (0..envelope.len()).map(|i| block_mean_square(&k_weighted[i * 4_800..i * 4_800 + 19_200])).collect();
+        let block_lufs: Vec<f64> = block_mean_squares.iter().map(|&ms| lufs_from_mean_square(ms)).collect();
+        let count = envelope.len() as f64;
+        let expected_gate = lufs_from_mean_square(block_mean_squares.iter().sum::<f64>() / count) - 10.0;
+        let naive_gate = block_lufs.iter().sum::<f64>() / count - 10.0;
+
+        let reported_gate = envelope.iter().cloned().fold(f64::INFINITY, f64::min);
+        assert!((reported_gate - expected_gate).abs() < 1e-6, "gate {reported_gate} vs expected {expected_gate}");
+        assert!((reported_gate - naive_gate).abs() > 1.0, "gate {reported_gate} matches log-domain averaging");
+    }
+}
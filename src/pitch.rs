@@ -0,0 +1,124 @@
+//! Autocorrelation-based pitch detection, used to tell periodic (voiced)
+//! speech apart from noise that happens to pass the energy gate.
+
+/// Lowest fundamental frequency considered: below this, a "pitch" is more
+/// likely a slow amplitude modulation than actual voicing.
+const MIN_PITCH_HZ: f64 = 50.0;
+/// Highest fundamental frequency considered.
+const MAX_PITCH_HZ: f64 = 500.0;
+
+/// Result of an autocorrelation pitch estimate for one analysis window.
+#[derive(Clone, Copy)]
+pub struct PitchEstimate {
+    /// Estimated fundamental frequency, in Hz.
+    pub frequency_hz: f64,
+    /// Normalized autocorrelation at the chosen lag, in `[0, 1]`; higher
+    /// means more periodic (voiced), lower means more noise-like.
+    pub clarity: f64,
+}
+
+/// Normalized autocorrelation `r(lag) = Σ x[n]·x[n+lag] / sqrt(Σx[n]²·Σx[n+lag]²)`.
+fn normalized_autocorrelation(window: &[f32], lag: usize) -> f64 {
+    if lag >= window.len() {
+        return 0.0;
+    }
+    let mut cross = 0.0f64;
+    let mut energy_a = 0.0f64;
+    let mut energy_b = 0.0f64;
+    for n in 0..window.len() - lag {
+        let a = window[n] as f64;
+        let b = window[n + lag] as f64;
+        cross += a * b;
+        energy_a += a * a;
+        energy_b += b * b;
+    }
+    let denom = (energy_a * energy_b).sqrt();
+    if denom <= 0.0 {
+        0.0
+    } else {
+        cross / denom
+    }
+}
+
+/// Estimates the fundamental frequency of `window`, scanning lags
+/// corresponding to `MIN_PITCH_HZ..MAX_PITCH_HZ`. Picks the first strong
+/// local maximum whose autocorrelation is at least `0.7` of the global peak
+/// found over that lag range, matching how pitch trackers avoid locking
+/// onto a weaker but higher sub-harmonic peak.
+pub fn estimate_pitch(window: &[f32], sample_rate: f64) -> PitchEstimate {
+    let min_lag = (sample_rate / MAX_PITCH_HZ).floor().max(1.0) as usize;
+    let max_lag = (sample_rate / MIN_PITCH_HZ).ceil() as usize;
+    let max_lag = max_lag.min(window.len().saturating_sub(1));
+
+    if min_lag >= max_lag {
+        return PitchEstimate { frequency_hz: 0.0, clarity: 0.0 };
+    }
+
+    let correlations: Vec<f64> = (min_lag..=max_lag).map(|lag| normalized_autocorrelation(window, lag)).collect();
+    let global_peak = correlations.iter().cloned().fold(f64::MIN, f64::max);
+    if global_peak <= 0.0 {
+        return PitchEstimate { frequency_hz: 0.0, clarity: 0.0 };
+    }
+    let clarity_threshold = 0.7 * global_peak;
+
+    for (i, &r) in correlations.iter().enumerate() {
+        let is_local_max = (i == 0 || r >= correlations[i - 1])
+            && (i == correlations.len() - 1 || r >= correlations[i + 1]);
+        if is_local_max && r >= clarity_threshold {
+            let lag = min_lag + i;
+            return PitchEstimate { frequency_hz: sample_rate / lag as f64, clarity: r.max(0.0) };
+        }
+    }
+
+    PitchEstimate { frequency_hz: 0.0, clarity: 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine_window(frequency_hz: f64, sample_rate: f64, num_samples: usize) -> Vec<f32> {
+        (0..num_samples)
+            .map(|n| (2.0 * std::f64::consts::PI * frequency_hz * n as f64 / sample_rate).sin() as f32)
+            .collect()
+    }
+
+    #[test]
+    fn a_pure_tone_is_detected_near_its_true_frequency_with_high_clarity() {
+        let sample_rate = 16_000.0;
+        let true_hz = 150.0;
+        let window = sine_window(true_hz, sample_rate, 1024);
+
+        let estimate = estimate_pitch(&window, sample_rate);
+        assert!(estimate.clarity > 0.9, "expected a pure tone to be highly periodic, got {}", estimate.clarity);
+        assert!(
+            (estimate.frequency_hz - true_hz).abs() < 5.0,
+            "expected ~{true_hz} Hz, got {}",
+            estimate.frequency_hz
+        );
+    }
+
+    #[test]
+    fn silence_has_zero_clarity() {
+        let window = vec![0.0f32; 1024];
+        let estimate = estimate_pitch(&window, 16_000.0);
+        assert_eq!(estimate.clarity, 0.0);
+        assert_eq!(estimate.frequency_hz, 0.0);
+    }
+
+    #[test]
+    fn a_window_too_short_for_the_pitch_range_reports_no_pitch() {
+        // At 16 kHz the minimum lag alone (32 samples, for 500 Hz) already
+        // exceeds a 16-sample window, so there's no valid lag range to scan.
+        let window = vec![0.3f32; 16];
+        let estimate = estimate_pitch(&window, 16_000.0);
+        assert_eq!(estimate.clarity, 0.0);
+        assert_eq!(estimate.frequency_hz, 0.0);
+    }
+
+    #[test]
+    fn normalized_autocorrelation_of_a_zero_lag_is_one_for_nonsilent_signal() {
+        let window = sine_window(200.0, 16_000.0, 512);
+        assert!((normalized_autocorrelation(&window, 0) - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,215 @@
+//! Polyphase sinc resampling, used to map an arbitrary input sample rate
+//! onto whatever rate the rest of the pipeline needs (e.g. 16 kHz for the
+//! Silero VAD backend, 48 kHz for LUFS measurement).
+
+/// Number of sub-phases in the precomputed filter bank. Higher values give
+/// finer fractional-delay resolution at the cost of more memory.
+const NUM_PHASES: usize = 256;
+
+/// Half-width of the windowed-sinc filter, in input samples on each side of
+/// the center tap. Wider windows give a sharper cutoff but cost more per
+/// output sample.
+const HALF_WIDTH: usize = 16;
+
+/// A precomputed bank of fractionally-shifted windowed-sinc filters, one
+/// per sub-phase, used to interpolate an output sample at any fractional
+/// input position without recomputing the sinc each time.
+pub struct SincResampler {
+    /// `filters[phase][tap]`, `phase` in `0..NUM_PHASES`, `tap` in
+    /// `0..2*HALF_WIDTH`.
+    filters: Vec<Vec<f32>>,
+}
+
+impl SincResampler {
+    /// Builds the filter bank. `cutoff` is the normalized cutoff frequency
+    /// (as a fraction of the input rate's Nyquist) used to
+    /// band-limit the sinc before resampling, avoiding aliasing when
+    /// downsampling.
+    pub fn new(cutoff: f64) -> Self {
+        let filters = (0..NUM_PHASES)
+            .map(|phase| {
+                let frac = phase as f64 / NUM_PHASES as f64;
+                (0..2 * HALF_WIDTH)
+                    .map(|tap| {
+                        // Offset of this tap from the fractional output
+                        // position, in input samples.
+                        let x = tap as f64 - HALF_WIDTH as f64 + 1.0 - frac;
+                        let sinc = if x.abs() < 1e-9 { 1.0 } else { (std::f64::consts::PI * cutoff * x).sin() / (std::f64::consts::PI * cutoff * x) };
+                        // Blackman window to taper the sinc's slow decay.
+                        let w = 0.42 - 0.5 * (2.0 * std::f64::consts::PI * tap as f64 / (2.0 * HALF_WIDTH as f64 - 1.0)).cos()
+                            + 0.08 * (4.0 * std::f64::consts::PI * tap as f64 / (2.0 * HALF_WIDTH as f64 - 1.0)).cos();
+                        (sinc * w * cutoff) as f32
+                    })
+                    .collect()
+            })
+            .collect();
+        SincResampler { filters }
+    }
+
+    /// Resamples `input` from `in_rate` to `out_rate`, returning the
+    /// resampled signal. Samples outside `input`'s bounds are treated as
+    /// silence (zero-padded).
+    pub fn resample(&self, input: &[f32], in_rate: f64, out_rate: f64) -> Vec<f32> {
+        if in_rate == out_rate || input.is_empty() {
+            return input.to_vec();
+        }
+        let ratio = in_rate / out_rate;
+        let out_len = (input.len() as f64 / ratio).floor() as usize;
+        let mut output = Vec::with_capacity(out_len);
+
+        for n in 0..out_len {
+            let in_pos = n as f64 * ratio;
+            let base = in_pos.floor() as isize;
+            let frac = in_pos - base as f64;
+            let phase = (frac * NUM_PHASES as f64).round() as usize % NUM_PHASES;
+            let filter = &self.filters[phase];
+
+            let mut acc = 0.0f32;
+            for (tap, &coeff) in filter.iter().enumerate() {
+                let sample_index = base - HALF_WIDTH as isize + 1 + tap as isize;
+                if sample_index >= 0 && (sample_index as usize) < input.len() {
+                    acc += coeff * input[sample_index as usize];
+                }
+            }
+            output.push(acc);
+        }
+        output
+    }
+}
+
+/// Resamples `input` from `in_rate` to `out_rate` using a cutoff that
+/// band-limits to the lower of the two rates, avoiding aliasing when
+/// downsampling. Convenience wrapper for one-off conversions.
+pub fn resample_to(input: &[f32], in_rate: f64, out_rate: f64) -> Vec<f32> {
+    if in_rate == out_rate {
+        return input.to_vec();
+    }
+    let cutoff = (out_rate / in_rate).min(1.0);
+    SincResampler::new(cutoff).resample(input, in_rate, out_rate)
+}
+
+/// Resampling quality, trading accuracy for speed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Quality {
+    /// Cubic (Catmull-Rom) interpolation. Cheap, but doesn't band-limit
+    /// before downsampling, so it's only appropriate when the input is
+    /// already well below the target Nyquist or some aliasing is
+    /// acceptable (e.g. a quick preview).
+    Fast,
+    /// The windowed-sinc polyphase resampler above. Slower, but band-limits
+    /// to avoid aliasing; this is what `resample_to` uses.
+    Sinc,
+}
+
+/// Resamples `input` from `in_rate` to `out_rate` via Catmull-Rom cubic
+/// interpolation at each output position, with no explicit band-limiting.
+fn resample_cubic(input: &[f32], in_rate: f64, out_rate: f64) -> Vec<f32> {
+    if input.is_empty() {
+        return Vec::new();
+    }
+    let ratio = in_rate / out_rate;
+    let out_len = (input.len() as f64 / ratio).floor() as usize;
+
+    let at = |index: isize| -> f32 {
+        if index < 0 {
+            input[0]
+        } else if (index as usize) < input.len() {
+            input[index as usize]
+        } else {
+            input[input.len() - 1]
+        }
+    };
+
+    (0..out_len)
+        .map(|n| {
+            let in_pos = n as f64 * ratio;
+            let base = in_pos.floor() as isize;
+            let frac = (in_pos - base as f64) as f32;
+
+            let p0 = at(base - 1);
+            let p1 = at(base);
+            let p2 = at(base + 1);
+            let p3 = at(base + 2);
+
+            // Catmull-Rom spline through p1..p2, parameterized by `frac`.
+            let a = -0.5 * p0 + 1.5 * p1 - 1.5 * p2 + 0.5 * p3;
+            let b = p0 - 2.5 * p1 + 2.0 * p2 - 0.5 * p3;
+            let c = -0.5 * p0 + 0.5 * p2;
+            let d = p1;
+
+            ((a * frac + b) * frac + c) * frac + d
+        })
+        .collect()
+}
+
+/// Resamples `input` from `in_rate` to `out_rate` at the requested
+/// `quality`. `Quality::Sinc` is equivalent to `resample_to`; `Quality::Fast`
+/// skips the filter bank entirely in favor of cheap cubic interpolation.
+pub fn resample_with_quality(input: &[f32], in_rate: f64, out_rate: f64, quality: Quality) -> Vec<f32> {
+    if in_rate == out_rate {
+        return input.to_vec();
+    }
+    match quality {
+        Quality::Fast => resample_cubic(input, in_rate, out_rate),
+        Quality::Sinc => resample_to(input, in_rate, out_rate),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pure tone should keep most of its energy when resampled with a
+    /// cutoff at or above its own frequency, and lose most of it when
+    /// downsampled past a cutoff below its frequency. Both checks are coarse:
+    /// a filter bank built with half the cutoff still passes them.
+    #[test]
+    fn sinc_resample_respects_the_requested_cutoff() {
+        let in_rate = 48_000.0;
+        let tone_hz = 1000.0;
+        let num_samples = 4800;
+        let tone: Vec<f32> = (0..num_samples)
+            .map(|n| (2.0 * std::f64::consts::PI * tone_hz * n as f64 / in_rate).sin() as f32)
+            .collect();
+
+        let rms = |samples: &[f32]| -> f64 {
+            let sum_sq: f64 = samples.iter().map(|&x| (x as f64) * (x as f64)).sum();
+            (sum_sq / samples.len() as f64).sqrt()
+        };
+        let input_rms = rms(&tone);
+
+        // Downsampling to 16 kHz keeps the tone well under the new Nyquist
+        // (8 kHz), so it should survive close to full amplitude.
+        let kept = resample_to(&tone, in_rate, 16_000.0);
+        assert!(rms(&kept) / input_rms > 0.9, "a tone under the target Nyquist should pass through");
+
+        // Downsampling to 1200 Hz puts the tone (1 kHz) above the new
+        // Nyquist (600 Hz), well past the cutoff, so it should be
+        // attenuated hard.
+        let attenuated = resample_to(&tone, in_rate, 1200.0);
+        assert!(rms(&attenuated) / input_rms < 0.3, "a tone past the target Nyquist should be attenuated");
+    }
+
+    #[test]
+    fn fast_quality_uses_cubic_interpolation_without_aliasing_guard() {
+        let in_rate = 48_000.0;
+        let out_rate = 24_000.0;
+        let ramp: Vec<f32> = (0..480).map(|n| n as f32).collect();
+
+        let sinc = resample_with_quality(&ramp, in_rate, out_rate, Quality::Sinc);
+        let fast = resample_with_quality(&ramp, in_rate, out_rate, Quality::Fast);
+
+        assert_eq!(sinc.len(), fast.len());
+        // Both should track the same roughly-linear ramp in the interior,
+        // just via different interpolation; they shouldn't diverge wildly.
+        // The sinc filter's first/last few output samples run out of
+        // input-side taps (the signal is implicitly zero-padded there), so
+        // it undershoots near the edges in a way cubic interpolation
+        // doesn't - exclude that boundary region rather than the whole-array
+        // comparison flaking on it.
+        let edge = 5;
+        for (a, b) in sinc[edge..sinc.len() - edge].iter().zip(&fast[edge..fast.len() - edge]) {
+            assert!((a - b).abs() < 5.0, "fast and sinc outputs diverged: {a} vs {b}");
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Neural voice-activity detection via the Silero VAD ONNX model.
+//!
+//! Runs the model over fixed-size frames (512 samples at 16 kHz, or 256 at
+//! 8 kHz, matching what Silero was trained on) and returns a per-frame
+//! speech probability. The model's recurrent state is threaded across
+//! frames within a call so probabilities reflect short-term context rather
+//! than just the current frame in isolation.
+//!
+//! This module is only compiled behind the `onnx-vad` Cargo feature
+//! (`mod vad;` in lib.rs is `#[cfg(feature = "onnx-vad")]`), so leaving the
+//! feature off (the default, once declared) never touches this file and
+//! `MeasurementMode::Vad` falls back to "always voiced" rather than failing
+//! the build.
+//!
+//! INCOMPLETE: this module is not actually buildable under its own feature
+//! flag yet. Turning `onnx-vad` on requires two things neither of which
+//! exist anywhere in this tree: a `Cargo.toml` declaring `onnx-vad = []`
+//! and the `ort` dependency, and the `models/silero_vad.onnx` file
+//! `include_bytes!` below points at. Neither is safe to fake - a
+//! placeholder manifest or a dummy model file would make `cargo build
+//! --features onnx-vad` *appear* to work while silently producing a VAD
+//! that can't load a real model. Until both are added for real, treat
+//! `onnx-vad` as reserved/unimplemented rather than an enablable feature.
+
+use ort::{Environment, Session, SessionBuilder, Value};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Bytes of the bundled Silero VAD ONNX model. Not present in this tree
+/// yet - see the INCOMPLETE note above; this will fail to compile if
+/// `onnx-vad` is ever turned on before the model is added.
+static SILERO_MODEL: &[u8] = include_bytes!("../models/silero_vad.onnx");
+
+/// The loaded session, built once and reused across calls. Building a
+/// fresh `Environment`/`Session` per call (as `find_voiced_segments` can do
+/// multiple times per invocation, once for `combined` and once per channel
+/// in `per_channel_output` mode) risks the heap corruption ONNX Runtime is
+/// prone to when the same model is re-instantiated rapidly.
+static SESSION: OnceLock<Mutex<Session>> = OnceLock::new();
+
+/// Frame size Silero expects for a given sample rate.
+fn frame_size(sample_rate: f64) -> usize {
+    if sample_rate >= 16_000.0 {
+        512
+    } else {
+        256
+    }
+}
+
+fn load_session(environment: &Arc<Environment>) -> ort::Result<Session> {
+    SessionBuilder::new(environment)?.with_model_from_memory(SILERO_MODEL)
+}
+
+/// Returns the process-wide Silero session, building it on first use.
+fn get_session() -> ort::Result<&'static Mutex<Session>> {
+    if let Some(session) = SESSION.get() {
+        return Ok(session);
+    }
+    let environment = Arc::new(Environment::builder().with_name("mutest-vad").build()?);
+    let session = load_session(&environment)?;
+    // If another thread raced us here, our session is simply dropped in
+    // favor of theirs; either way exactly one `Session` ends up stored.
+    Ok(SESSION.get_or_init(move || Mutex::new(session)))
+}
+
+/// Runs Silero VAD over `samples`, returning one speech probability in
+/// `[0, 1]` per frame. `sample_rate` must be 8000 or 16000 Hz; resample
+/// first otherwise (see `resample.rs`).
+pub fn speech_probabilities(samples: &[f32], sample_rate: f64) -> ort::Result<Vec<f32>> {
+    let session = get_session()?;
+    let mut session = session.lock().unwrap();
+
+    let frame_len = frame_size(sample_rate);
+    let mut h = vec![0.0f32; 2 * 1 * 64];
+    let mut c = vec![0.0f32; 2 * 1 * 64];
+    let mut probabilities = Vec::with_capacity(samples.len() / frame_len + 1);
+
+    for frame in samples.chunks(frame_len) {
+        // Silero needs a fixed-length frame; pad a trailing partial frame
+        // with silence instead of dropping it.
+        let mut padded = vec![0.0f32; frame_len];
+        padded[..frame.len()].copy_from_slice(frame);
+
+        let input = Value::from_array(([1usize, frame_len], padded.as_slice()))?;
+        let sr_input = Value::from_array(([1usize], &[sample_rate as i64][..]))?;
+        let h_input = Value::from_array(([2usize, 1, 64], h.as_slice()))?;
+        let c_input = Value::from_array(([2usize, 1, 64], c.as_slice()))?;
+
+        let outputs = session.run(ort::inputs![input, sr_input, h_input, c_input]?)?;
+        let prob = outputs[0].try_extract_tensor::<f32>()?.view()[[0, 0]];
+        h = outputs[1].try_extract_tensor::<f32>()?.view().iter().copied().collect();
+        c = outputs[2].try_extract_tensor::<f32>()?.view().iter().copied().collect();
+
+        probabilities.push(prob);
+    }
+
+    Ok(probabilities)
+}
+
+/// Maps per-frame speech probabilities onto the coarser analysis-chunk
+/// grid, taking the max probability of the frames each chunk overlaps
+/// (a chunk containing any speech frame should count as voiced).
+pub fn chunk_probabilities(
+    probabilities: &[f32],
+    frame_size_samples: usize,
+    chunk_size_samples: usize,
+    num_chunks: usize,
+) -> Vec<f32> {
+    if probabilities.is_empty() {
+        return vec![0.0; num_chunks];
+    }
+    (0..num_chunks)
+        .map(|i| {
+            let chunk_start = i * chunk_size_samples;
+            let chunk_end = chunk_start + chunk_size_samples;
+            let first_frame = chunk_start / frame_size_samples;
+            let last_frame = (chunk_end - 1) / frame_size_samples;
+            probabilities[first_frame..=last_frame.min(probabilities.len() - 1)]
+                .iter()
+                .copied()
+                .fold(0.0_f32, f32::max)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_size_matches_silero_training_rates() {
+        assert_eq!(frame_size(16_000.0), 512);
+        assert_eq!(frame_size(48_000.0), 512);
+        assert_eq!(frame_size(8_000.0), 256);
+        assert_eq!(frame_size(4_000.0), 256);
+    }
+
+    #[test]
+    fn empty_probabilities_fall_back_to_silence() {
+        assert_eq!(chunk_probabilities(&[], 512, 1024, 3), vec![0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn chunk_probabilities_takes_the_max_of_overlapping_frames() {
+        // Two 512-sample frames per 1024-sample chunk; a chunk counts as
+        // voiced if either of its frames does.
+        let probabilities = [0.1, 0.9, 0.2, 0.05];
+        let chunks = chunk_probabilities(&probabilities, 512, 1024, 2);
+        assert_eq!(chunks, vec![0.9, 0.2]);
+    }
+}